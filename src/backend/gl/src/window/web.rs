@@ -3,7 +3,8 @@ use arrayvec::ArrayVec;
 use glow::Context as _;
 use hal::{adapter::Adapter, format as f, image, window};
 use std::iter;
-use web_sys::{WebGl2RenderingContext, HtmlCanvasElement};
+use std::rc::Rc;
+use web_sys::{WebGl2RenderingContext, HtmlCanvasElement, OffscreenCanvas};
 use wasm_bindgen::JsCast;
 
 #[derive(Clone, Debug)]
@@ -15,14 +16,146 @@ struct PixelFormat {
     multisampling: Option<u32>,
 }
 
+/// The DOM object backing a [`Surface`].
+///
+/// Most gfx applications run on the main thread and create their canvas
+/// from `document`, but an `OffscreenCanvas` lets gfx be driven from a Web
+/// Worker, where there is no DOM to query.
+#[derive(Clone, Debug)]
+pub enum Canvas {
+    Element(HtmlCanvasElement),
+    Offscreen(OffscreenCanvas),
+}
+
+impl Canvas {
+    fn width(&self) -> u32 {
+        match self {
+            Canvas::Element(canvas) => canvas.width(),
+            Canvas::Offscreen(canvas) => canvas.width(),
+        }
+    }
+
+    fn height(&self) -> u32 {
+        match self {
+            Canvas::Element(canvas) => canvas.height(),
+            Canvas::Offscreen(canvas) => canvas.height(),
+        }
+    }
+}
+
+/// The GPU power preference to request from the browser, per the
+/// `WebGLContextAttributes.powerPreference` dictionary member.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerPreference {
+    Default,
+    LowPower,
+    HighPerformance,
+}
+
+impl PowerPreference {
+    fn as_str(self) -> &'static str {
+        match self {
+            PowerPreference::Default => "default",
+            PowerPreference::LowPower => "low-power",
+            PowerPreference::HighPerformance => "high-performance",
+        }
+    }
+}
+
+/// Configuration for the WebGL2 context created by [`Instance::create`] and
+/// [`Instance::create_from_offscreen_canvas`].
+///
+/// Mirrors the standard `WebGLContextAttributes` dictionary, so callers can
+/// opt into behavior the backend does not select by default, such as
+/// `desynchronized` for low-latency presentation or a `power_preference` of
+/// `HighPerformance` to avoid being handed an integrated GPU.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WebGlContextOptions {
+    pub alpha: bool,
+    pub depth: bool,
+    pub stencil: bool,
+    pub antialias: bool,
+    pub premultiplied_alpha: bool,
+    pub preserve_drawing_buffer: bool,
+    pub power_preference: PowerPreference,
+    pub desynchronized: bool,
+    pub fail_if_major_performance_caveat: bool,
+}
+
+impl Default for WebGlContextOptions {
+    fn default() -> Self {
+        WebGlContextOptions {
+            alpha: true,
+            depth: true,
+            stencil: false,
+            antialias: false,
+            premultiplied_alpha: true,
+            preserve_drawing_buffer: false,
+            power_preference: PowerPreference::Default,
+            desynchronized: false,
+            fail_if_major_performance_caveat: false,
+        }
+    }
+}
+
+impl WebGlContextOptions {
+    fn to_js_object(&self) -> js_sys::Object {
+        let context_options = js_sys::Object::new();
+        js_sys::Reflect::set(&context_options, &"alpha".into(), &self.alpha.into())
+            .expect("Cannot create context options");
+        js_sys::Reflect::set(&context_options, &"depth".into(), &self.depth.into())
+            .expect("Cannot create context options");
+        js_sys::Reflect::set(&context_options, &"stencil".into(), &self.stencil.into())
+            .expect("Cannot create context options");
+        js_sys::Reflect::set(&context_options, &"antialias".into(), &self.antialias.into())
+            .expect("Cannot create context options");
+        js_sys::Reflect::set(
+            &context_options,
+            &"premultipliedAlpha".into(),
+            &self.premultiplied_alpha.into(),
+        ).expect("Cannot create context options");
+        js_sys::Reflect::set(
+            &context_options,
+            &"preserveDrawingBuffer".into(),
+            &self.preserve_drawing_buffer.into(),
+        ).expect("Cannot create context options");
+        js_sys::Reflect::set(
+            &context_options,
+            &"powerPreference".into(),
+            &self.power_preference.as_str().into(),
+        ).expect("Cannot create context options");
+        js_sys::Reflect::set(
+            &context_options,
+            &"desynchronized".into(),
+            &self.desynchronized.into(),
+        ).expect("Cannot create context options");
+        js_sys::Reflect::set(
+            &context_options,
+            &"failIfMajorPerformanceCaveat".into(),
+            &self.fail_if_major_performance_caveat.into(),
+        ).expect("Cannot create context options");
+        context_options
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Instance {
     context: Starc<WebGl2RenderingContext>,
-    canvas: Starc<HtmlCanvasElement>,
+    canvas: Option<Starc<Canvas>>,
 }
 
 impl Instance {
-    pub fn create(_name: &str, _version: u32) -> Result<Self, hal::UnsupportedBackend> {
+    pub fn create(name: &str, version: u32) -> Result<Self, hal::UnsupportedBackend> {
+        Self::create_with_options(name, version, WebGlContextOptions::default())
+    }
+
+    /// Like [`Instance::create`], but lets the caller configure the WebGL2
+    /// context instead of relying on the backend's defaults.
+    pub fn create_with_options(
+        _name: &str,
+        _version: u32,
+        options: WebGlContextOptions,
+    ) -> Result<Self, hal::UnsupportedBackend> {
         let document = web_sys::window()
             .and_then(|win| win.document())
             .expect("Cannot get document");
@@ -31,32 +164,88 @@ impl Instance {
             .expect("Cannot create canvas")
             .dyn_into::<HtmlCanvasElement>()
             .expect("Cannot get canvas element");
-        let context_options = js_sys::Object::new();
-        js_sys::Reflect::set(
-            &context_options,
-            &"antialias".into(),
-            &wasm_bindgen::JsValue::FALSE,
-        ).expect("Cannot create context options");
         let context = canvas
-            .get_context_with_context_options("webgl2", &context_options)
+            .get_context_with_context_options("webgl2", &options.to_js_object())
             .expect("Cannot create WebGL2 context")
             .and_then(|context| context.dyn_into::<WebGl2RenderingContext>().ok())
             .expect("Cannot convert into WebGL2 context");
         Ok(Instance {
             context: Starc::new(context),
-            canvas: Starc::new(canvas),
+            canvas: Some(Starc::new(Canvas::Element(canvas))),
         })
     }
 
-    pub fn create_surface_with_element(&self) -> (Surface, HtmlCanvasElement) {
-        (
-            Surface {
-                canvas: Starc::clone(&self.canvas),
-                swapchain: None,
-                renderbuffer: None,
+    /// Creates an instance around an already-created WebGL2 context, rather
+    /// than having gfx create its own canvas and context.
+    ///
+    /// This is the entry point for WebXR: an `XRWebGLLayer` owns the
+    /// context it is constructed from, and `canvas` may not be available
+    /// (or meaningful) when the context belongs to a Web Worker.
+    pub fn create_from_context(
+        context: WebGl2RenderingContext,
+        canvas: Option<HtmlCanvasElement>,
+    ) -> Result<Self, hal::UnsupportedBackend> {
+        Ok(Instance {
+            context: Starc::new(context),
+            canvas: canvas.map(|canvas| Starc::new(Canvas::Element(canvas))),
+        })
+    }
+
+    /// Creates an instance around an `OffscreenCanvas`, for use from a Web
+    /// Worker where `document` is unavailable.
+    pub fn create_from_offscreen_canvas(
+        canvas: OffscreenCanvas,
+    ) -> Result<Self, hal::UnsupportedBackend> {
+        Self::create_from_offscreen_canvas_with_options(canvas, WebGlContextOptions::default())
+    }
+
+    /// Like [`Instance::create_from_offscreen_canvas`], but lets the caller
+    /// configure the WebGL2 context instead of relying on the backend's
+    /// defaults.
+    pub fn create_from_offscreen_canvas_with_options(
+        canvas: OffscreenCanvas,
+        options: WebGlContextOptions,
+    ) -> Result<Self, hal::UnsupportedBackend> {
+        let context = canvas
+            .get_context_with_context_options("webgl2", &options.to_js_object())
+            .expect("Cannot create WebGL2 context")
+            .and_then(|context| context.dyn_into::<WebGl2RenderingContext>().ok())
+            .expect("Cannot convert into WebGL2 context");
+        Ok(Instance {
+            context: Starc::new(context),
+            canvas: Some(Starc::new(Canvas::Offscreen(canvas))),
+        })
+    }
+
+    pub fn create_surface(&self) -> Surface {
+        Surface {
+            context: Starc::clone(&self.context),
+            canvas: self.canvas.clone(),
+            swapchain: None,
+            renderbuffers: ArrayVec::new(),
+            resolve_renderbuffers: ArrayVec::new(),
+            external_framebuffer: None,
+            pixel_format: PixelFormat {
+                color_bits: 32,
+                alpha_bits: 8,
+                srgb: false,
+                double_buffer: true,
+                multisampling: None,
             },
-            (*self.canvas).clone(),
-        )
+        }
+    }
+
+    pub fn create_surface_with_element(&self) -> (Surface, HtmlCanvasElement) {
+        let canvas = match self.canvas.as_deref() {
+            Some(Canvas::Element(canvas)) => canvas.clone(),
+            Some(Canvas::Offscreen(_)) => panic!(
+                "Instance was created from an OffscreenCanvas; use `create_surface` instead"
+            ),
+            None => panic!(
+                "Instance has no canvas (it was created via `create_from_context`); use `create_surface` instead"
+            ),
+        };
+        (self.create_surface(), canvas)
     }
 
 }
@@ -72,32 +261,246 @@ impl hal::Instance for Instance {
 #[derive(Clone, Debug)]
 pub struct Swapchain {
     pub(crate) extent: window::Extent2D,
+    /// One render-target FBO per swapchain image (the MSAA target, when
+    /// multisampling; otherwise the presented image itself).
     pub(crate) fbos: ArrayVec<[native::RawFrameBuffer; 3]>,
+    /// The single-sample resolve FBO for each entry of `fbos`, when the
+    /// swapchain was configured with MSAA. Empty when single-sampled, in
+    /// which case `fbos` is presented directly.
+    resolve_fbos: ArrayVec<[native::RawFrameBuffer; 3]>,
+    /// The externally-owned framebuffer `Surface::external_framebuffer`
+    /// returned on the last `acquire_image`, if any. Takes priority over
+    /// `resolve_fbos` as the present target.
+    external_fbo: Option<native::RawFrameBuffer>,
+    /// Index into `fbos`/`resolve_fbos` that the next `acquire_image` will
+    /// hand out.
+    next_image: usize,
+    /// Index most recently handed out by `acquire_image`; this is what
+    /// `resolve_current` operates on.
+    current_image: usize,
+    /// Set once the first image has been handed out, so `acquire_image`
+    /// knows whether `current_image` actually refers to an image that was
+    /// rendered into (and so needs resolving) or is just its zero-value
+    /// default from `configure_swapchain`.
+    has_pending_image: bool,
+    /// A GPU fence per image slot, inserted by `resolve_current` after that
+    /// slot's presentation work and signalled once the GPU has finished
+    /// it. `acquire_image` waits on
+    /// it before handing the slot out again, so the caller never renders
+    /// into an image the GPU hasn't finished presenting yet.
+    fences: ArrayVec<[Option<native::Fence>; 3]>,
+    /// Cloned from `Device::share::context` at `configure_swapchain` time,
+    /// so `acquire_image` can wait on `fences`, and `resolve_current` can
+    /// blit and arm them, without needing a `&Device` (the
+    /// `PresentationSurface::acquire_image` signature doesn't pass one).
+    context: GlContainer,
+}
+
+impl Swapchain {
+    /// Resolves the image at `current_image` into its presentation target:
+    /// `external_fbo` if one was captured for it (e.g. a WebXR opaque
+    /// framebuffer), otherwise the owned single-sample resolve target
+    /// allocated for MSAA. No blit is issued when neither applies, since the
+    /// image is then presentable directly. Either way, arms this slot's
+    /// fence so a future `acquire_image` knows when it's safe to reuse.
+    ///
+    /// There's no explicit present call in this backend (WebGL has no
+    /// analog of `vkQueuePresentKHR`; the browser composites whatever was
+    /// last drawn), so `acquire_image` calls this itself, once per image,
+    /// right before rotating to the next one: by the time a caller asks
+    /// for a new image, it must be done recording and submitting work
+    /// against the previous one.
+    unsafe fn resolve_current(&mut self) {
+        let index = self.current_image;
+
+        let resolve_fbo = self
+            .external_fbo
+            .or_else(|| self.resolve_fbos.get(index).copied());
+        if let Some(resolve_fbo) = resolve_fbo {
+            self.context
+                .bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.fbos[index]));
+            self.context
+                .bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(resolve_fbo));
+            self.context.blit_framebuffer(
+                0,
+                0,
+                self.extent.width as i32,
+                self.extent.height as i32,
+                0,
+                0,
+                self.extent.width as i32,
+                self.extent.height as i32,
+                glow::COLOR_BUFFER_BIT,
+                glow::NEAREST,
+            );
+        }
+
+        if let Some(old) = self.fences[index].take() {
+            self.context.delete_sync(old);
+        }
+        self.fences[index] = Some(
+            self.context
+                .fence_sync(glow::SYNC_GPU_COMMANDS_COMPLETE, 0)
+                .unwrap(),
+        );
+    }
+}
+
+/// Converts a `timeout_ns` as passed to `acquire_image` into the timeout
+/// `client_wait_sync` expects. `!0` is hal's "wait indefinitely" sentinel;
+/// the `min(i32::MAX)` clamp below would silently cap it at ~2.147s instead,
+/// so map it onto `-1`, which GL/WebGL2 treat as an infinite wait.
+fn gl_wait_timeout_ns(timeout_ns: u64) -> i32 {
+    if timeout_ns == !0 {
+        -1
+    } else {
+        timeout_ns.min(i32::MAX as u64) as i32
+    }
 }
 
 impl window::Swapchain<B> for Swapchain {
     unsafe fn acquire_image(
         &mut self,
-        _timeout_ns: u64,
-        _semaphore: Option<&native::Semaphore>,
-        _fence: Option<&native::Fence>,
+        timeout_ns: u64,
+        semaphore: Option<&native::Semaphore>,
+        fence: Option<&native::Fence>,
     ) -> Result<(window::SwapImageIndex, Option<window::Suboptimal>), window::AcquireError> {
-        // TODO: sync
-        Ok((0, None))
+        if self.has_pending_image {
+            self.resolve_current();
+        }
+
+        let index = self.next_image;
+
+        if let Some(sync) = self.fences[index].take() {
+            let status = self.context.client_wait_sync(
+                sync,
+                glow::SYNC_FLUSH_COMMANDS_BIT,
+                gl_wait_timeout_ns(timeout_ns),
+            );
+            self.context.delete_sync(sync);
+            if status == glow::TIMEOUT_EXPIRED || status == glow::WAIT_FAILED {
+                return Err(window::AcquireError::Timeout);
+            }
+        }
+
+        self.next_image = (index + 1) % self.fbos.len();
+        self.current_image = index;
+        self.has_pending_image = true;
+
+        // The wait above (if any) already blocked until the GPU caught up
+        // with this image slot, so it's safe to signal the caller's
+        // fence/semaphore now instead of leaving them untouched.
+        if let Some(semaphore) = semaphore {
+            semaphore.signal();
+        }
+        if let Some(fence) = fence {
+            fence.signal();
+        }
+
+        Ok((index as window::SwapImageIndex, None))
     }
 }
 
-#[derive(Clone, Debug)]
+/// The sample counts `Surface::supported_sample_counts` checks the
+/// context's actual `MAX_SAMPLES` against. WebGL2 only guarantees 4 (the
+/// spec's floor), but many contexts support more; these are the power-of-two
+/// counts renderbuffer storage is commonly asked for.
+const CANDIDATE_SAMPLE_COUNTS: &[u32] = &[1, 2, 4, 8, 16];
+
+#[derive(Clone)]
 pub struct Surface {
-    canvas: Starc<web_sys::HtmlCanvasElement>,
+    context: Starc<WebGl2RenderingContext>,
+    canvas: Option<Starc<Canvas>>,
     pub(crate) swapchain: Option<Swapchain>,
-    renderbuffer: Option<native::Renderbuffer>,
+    /// One color renderbuffer per swapchain image, attached to the
+    /// matching entry of `Swapchain::fbos`.
+    renderbuffers: ArrayVec<[native::Renderbuffer; 3]>,
+    /// One single-sample resolve renderbuffer per swapchain image,
+    /// attached to the matching entry of `Swapchain::resolve_fbos`. Empty
+    /// when not multisampling.
+    resolve_renderbuffers: ArrayVec<[native::Renderbuffer; 3]>,
+    /// When set, the swapchain presents by blitting into the framebuffer
+    /// this returns instead of into `resolve_renderbuffers`. Re-invoked on
+    /// every `acquire_image`, since e.g. a WebXR runtime may hand back a
+    /// freshly recreated opaque framebuffer each frame.
+    external_framebuffer: Option<Rc<dyn Fn() -> native::RawFrameBuffer>>,
+    pixel_format: PixelFormat,
+}
+
+impl std::fmt::Debug for Surface {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Surface")
+            .field("canvas", &self.canvas)
+            .field("swapchain", &self.swapchain)
+            .field("renderbuffers", &self.renderbuffers)
+            .field("resolve_renderbuffers", &self.resolve_renderbuffers)
+            .field("has_external_framebuffer", &self.external_framebuffer.is_some())
+            .field("pixel_format", &self.pixel_format)
+            .finish()
+    }
 }
 
 impl Surface {
     fn swapchain_formats(&self) -> Vec<f::Format> {
         vec![f::Format::Rgba8Unorm, f::Format::Bgra8Unorm]
     }
+
+    /// Requests the given MSAA sample count be used the next time the
+    /// swapchain is (re)configured. `None` or `Some(1)` both mean
+    /// single-sampled; pass `None` to go back to single-sampling.
+    pub fn set_samples(&mut self, samples: Option<u32>) {
+        self.pixel_format.multisampling = samples.filter(|&samples| samples > 1);
+    }
+
+    /// The renderbuffer sample counts this surface can be configured with,
+    /// queried from the context's actual `MAX_SAMPLES` rather than assumed
+    /// from the WebGL2 spec's minimum.
+    pub fn supported_sample_counts(&self) -> Vec<u32> {
+        let max_samples = self
+            .context
+            .get_parameter(WebGl2RenderingContext::MAX_SAMPLES)
+            .ok()
+            .and_then(|value| value.as_f64())
+            .map_or(1, |value| value as u32);
+        CANDIDATE_SAMPLE_COUNTS
+            .iter()
+            .copied()
+            .filter(|&samples| samples <= max_samples)
+            .collect()
+    }
+
+    /// Presents into the framebuffer `provider` returns instead of the
+    /// canvas, e.g. an `XRWebGLLayer`'s opaque framebuffer. `provider` is
+    /// called once per `acquire_image`.
+    ///
+    /// The swapchain still renders into its own owned renderbuffer and
+    /// blits the result into `provider`'s framebuffer at present time,
+    /// rather than rendering into it directly: `acquire_image` hands back
+    /// a `native::ImageView::Renderbuffer`, and that's the only kind of
+    /// image this backend's render-pass machinery currently knows how to
+    /// target, so there's nowhere to plug in an externally-owned
+    /// framebuffer as the render target itself. WebXR's opaque
+    /// framebuffers are also not writable through `framebufferRenderbuffer`
+    /// (the spec forbids attaching to them), which rules out adopting
+    /// `provider`'s framebuffer as our own FBO even if the image-view type
+    /// allowed it. The extra blit is the cost of bridging that gap.
+    pub fn set_external_framebuffer(
+        &mut self,
+        provider: impl Fn() -> native::RawFrameBuffer + 'static,
+    ) {
+        self.external_framebuffer = Some(Rc::new(provider));
+    }
+
+    /// Goes back to presenting through the surface's own canvas.
+    pub fn clear_external_framebuffer(&mut self) {
+        self.external_framebuffer = None;
+        // Drop the last framebuffer the old provider handed back too, so a
+        // configured swapchain stops blitting into it immediately instead
+        // of on the next acquire_image.
+        if let Some(swapchain) = self.swapchain.as_mut() {
+            swapchain.external_fbo = None;
+        }
+    }
 }
 
 impl window::Surface<B> for Surface {
@@ -109,11 +512,22 @@ impl window::Surface<B> for Surface {
         Option<Vec<f::Format>>,
         Vec<window::PresentMode>,
     ) {
-        let extent = hal::window::Extent2D {
-            width: self.canvas.width(),
-            height: self.canvas.height(),
+        // When there's no canvas (e.g. a surface built from a WebXR or
+        // worker-owned context via `Instance::create_from_context`), fall
+        // back to the drawing buffer's own size.
+        let extent = match &self.canvas {
+            Some(canvas) => hal::window::Extent2D {
+                width: canvas.width(),
+                height: canvas.height(),
+            },
+            None => hal::window::Extent2D {
+                width: self.context.drawing_buffer_width() as u32,
+                height: self.context.drawing_buffer_height() as u32,
+            },
         };
 
+        // See `Surface::supported_sample_counts` for the MSAA sample counts
+        // this surface can be configured with via `Surface::set_samples`.
         let caps = window::SurfaceCapabilities {
             image_count: 2 ..= 2,
             current_extent: Some(extent),
@@ -148,32 +562,98 @@ impl window::PresentationSurface<B> for Surface {
             for fbo in old.fbos {
                 gl.delete_framebuffer(fbo);
             }
+            for fbo in old.resolve_fbos {
+                gl.delete_framebuffer(fbo);
+            }
+            for fence in old.fences.into_iter().flatten() {
+                gl.delete_sync(fence);
+            }
         }
-
-        if self.renderbuffer.is_none() {
-            self.renderbuffer = Some(gl.create_renderbuffer().unwrap());
+        for rbo in self.renderbuffers.drain(..) {
+            gl.delete_renderbuffer(rbo);
+        }
+        for rbo in self.resolve_renderbuffers.drain(..) {
+            gl.delete_renderbuffer(rbo);
         }
 
         let desc = conv::describe_format(config.format).unwrap();
-        gl.bind_renderbuffer(glow::RENDERBUFFER, self.renderbuffer);
-        gl.renderbuffer_storage(
-            glow::RENDERBUFFER,
-            desc.tex_internal,
-            config.extent.width as i32,
-            config.extent.height as i32,
-        );
+        let samples = self.pixel_format.multisampling.unwrap_or(1);
+        // `fbos`/`resolve_fbos` are capped at 3 entries (see their
+        // `ArrayVec` capacity), matching the triple-buffering this backend
+        // supports at most.
+        let image_count = (config.image_count as usize).clamp(1, 3);
+
+        let mut fbos = ArrayVec::new();
+        let mut resolve_fbos = ArrayVec::new();
+
+        for _ in 0 .. image_count {
+            let renderbuffer = gl.create_renderbuffer().unwrap();
+            gl.bind_renderbuffer(glow::RENDERBUFFER, Some(renderbuffer));
+            if samples > 1 {
+                gl.renderbuffer_storage_multisample(
+                    glow::RENDERBUFFER,
+                    samples as i32,
+                    desc.tex_internal,
+                    config.extent.width as i32,
+                    config.extent.height as i32,
+                );
+            } else {
+                gl.renderbuffer_storage(
+                    glow::RENDERBUFFER,
+                    desc.tex_internal,
+                    config.extent.width as i32,
+                    config.extent.height as i32,
+                );
+            }
+            self.renderbuffers.push(renderbuffer);
+
+            let fbo = gl.create_framebuffer().unwrap();
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(fbo));
+            gl.framebuffer_renderbuffer(
+                glow::READ_FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::RENDERBUFFER,
+                Some(renderbuffer),
+            );
+            fbos.push(fbo);
+
+            // When multisampling, `fbo` above is the MSAA render target and
+            // can't be presented directly; allocate a single-sample
+            // resolve target that `Swapchain::resolve_current` blits into
+            // before present.
+            if samples > 1 {
+                let resolve_rbo = gl.create_renderbuffer().unwrap();
+                gl.bind_renderbuffer(glow::RENDERBUFFER, Some(resolve_rbo));
+                gl.renderbuffer_storage(
+                    glow::RENDERBUFFER,
+                    desc.tex_internal,
+                    config.extent.width as i32,
+                    config.extent.height as i32,
+                );
+                self.resolve_renderbuffers.push(resolve_rbo);
+
+                let resolve_fbo = gl.create_framebuffer().unwrap();
+                gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(resolve_fbo));
+                gl.framebuffer_renderbuffer(
+                    glow::DRAW_FRAMEBUFFER,
+                    glow::COLOR_ATTACHMENT0,
+                    glow::RENDERBUFFER,
+                    Some(resolve_rbo),
+                );
+                resolve_fbos.push(resolve_fbo);
+            }
+        }
 
-        let fbo = gl.create_framebuffer().unwrap();
-        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(fbo));
-        gl.framebuffer_renderbuffer(
-            glow::READ_FRAMEBUFFER,
-            glow::COLOR_ATTACHMENT0,
-            glow::RENDERBUFFER,
-            self.renderbuffer,
-        );
         self.swapchain = Some(Swapchain {
             extent: config.extent,
-            fbos: iter::once(fbo).collect(),
+            fbos,
+            resolve_fbos,
+            external_fbo: None,
+            next_image: 0,
+            current_image: 0,
+            has_pending_image: false,
+            fences: iter::repeat(None).take(image_count).collect(),
+            context: gl.clone(),
         });
 
         Ok(())
@@ -185,17 +665,71 @@ impl window::PresentationSurface<B> for Surface {
             for fbo in old.fbos {
                 gl.delete_framebuffer(fbo);
             }
+            for fbo in old.resolve_fbos {
+                gl.delete_framebuffer(fbo);
+            }
+            for fence in old.fences.into_iter().flatten() {
+                gl.delete_sync(fence);
+            }
         }
-        if let Some(rbo) = self.renderbuffer.take() {
+        for rbo in self.renderbuffers.drain(..) {
+            gl.delete_renderbuffer(rbo);
+        }
+        for rbo in self.resolve_renderbuffers.drain(..) {
             gl.delete_renderbuffer(rbo);
         }
     }
 
     unsafe fn acquire_image(
         &mut self,
-        _timeout_ns: u64,
+        timeout_ns: u64,
     ) -> Result<(Self::SwapchainImage, Option<window::Suboptimal>), window::AcquireError> {
-        let image = native::ImageView::Renderbuffer(self.renderbuffer.unwrap());
+        let swapchain = self
+            .swapchain
+            .as_mut()
+            .expect("swapchain must be configured before acquiring an image");
+
+        // There's no explicit present call in this backend, so resolving
+        // the previously acquired image (MSAA blit / external-framebuffer
+        // blit / arming its reuse fence) happens here, right before we
+        // rotate to the next one. See `Swapchain::resolve_current`.
+        if swapchain.has_pending_image {
+            swapchain.resolve_current();
+        }
+
+        let index = swapchain.next_image;
+        swapchain.next_image = (index + 1) % swapchain.fbos.len();
+        swapchain.current_image = index;
+        swapchain.has_pending_image = true;
+
+        // Throttle to the number of images in flight: don't hand back a
+        // renderbuffer the GPU might still be presenting from the last
+        // time this slot was used.
+        if let Some(sync) = swapchain.fences[index].take() {
+            let status = swapchain.context.client_wait_sync(
+                sync,
+                glow::SYNC_FLUSH_COMMANDS_BIT,
+                gl_wait_timeout_ns(timeout_ns),
+            );
+            swapchain.context.delete_sync(sync);
+            if status == glow::TIMEOUT_EXPIRED || status == glow::WAIT_FAILED {
+                return Err(window::AcquireError::Timeout);
+            }
+        }
+
+        swapchain.next_image = (index + 1) % swapchain.fbos.len();
+        swapchain.current_image = index;
+        swapchain.has_pending_image = true;
+
+        // The XR runtime (or whatever owns `external_framebuffer`) may hand
+        // back a freshly recreated framebuffer every frame, so re-query it
+        // on every acquire rather than caching it at configure time. Also
+        // clear it back out once the caller stops supplying a provider, so
+        // a cleared external framebuffer doesn't keep getting blitted into.
+        swapchain.external_fbo = self.external_framebuffer.as_ref().map(|provider| provider());
+
+        let image = native::ImageView::Renderbuffer(self.renderbuffers[index]);
         Ok((image, None))
     }
 }
+